@@ -0,0 +1,315 @@
+//! Deserializing a parsed config map into a caller's own `#[derive(Deserialize)]`
+//! struct, without them having to hand-walk `ConfigValue::Table`s.
+
+use std::collections::btree_map;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer, Error as _, MapAccess, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::{ConfigError, ConfigValue};
+
+impl de::Error for ConfigError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ConfigError {
+            message: msg.to_string(),
+            line: 0,
+            column: 0,
+        }
+    }
+}
+
+/// Deserialize a parsed config map into `T`, coercing each leaf string to the
+/// target field type (so a field typed `u16` or `bool` parses the string
+/// `"65535"`/`"true"` the same way the schema validator does).
+pub fn from_config<T: DeserializeOwned>(
+    map: &BTreeMap<String, ConfigValue>,
+) -> Result<T, ConfigError> {
+    T::deserialize(MapDeserializer { map })
+}
+
+struct MapDeserializer<'a> {
+    map: &'a BTreeMap<String, ConfigValue>,
+}
+
+impl<'de, 'a> Deserializer<'de> for MapDeserializer<'a> {
+    type Error = ConfigError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(ConfigMapAccess {
+            iter: self.map.iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct enum identifier ignored_any
+    }
+}
+
+struct ConfigMapAccess<'a> {
+    iter: btree_map::Iter<'a, String, ConfigValue>,
+    value: Option<&'a ConfigValue>,
+}
+
+impl<'de, 'a> MapAccess<'de> for ConfigMapAccess<'a> {
+    type Error = ConfigError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(de::value::StrDeserializer::new(key))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(ValueDeserializer { value })
+    }
+}
+
+struct ValueDeserializer<'a> {
+    value: &'a ConfigValue,
+}
+
+/// `i64`/`u64`-style targets: take a native `Int`, or fall back to parsing a
+/// `Str` the way the schema validator does.
+macro_rules! deserialize_int {
+    ($method:ident, $visit:ident, $ty:ty) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            match self.value {
+                ConfigValue::Int(i) => {
+                    let parsed = <$ty>::try_from(*i).map_err(|_| {
+                        ConfigError::custom(format!("{} is out of range for {}", i, stringify!($ty)))
+                    })?;
+                    visitor.$visit(parsed)
+                }
+                ConfigValue::Str(s) => {
+                    let parsed: $ty = s.parse().map_err(|_| {
+                        ConfigError::custom(format!("expected a {}, got: '{}'", stringify!($ty), s))
+                    })?;
+                    visitor.$visit(parsed)
+                }
+                other => Err(ConfigError::custom(format!(
+                    "expected a {}, got: {:?}",
+                    stringify!($ty),
+                    other
+                ))),
+            }
+        }
+    };
+}
+
+/// `f32`/`f64`-style targets: take a native `Float` or `Int`, or fall back to
+/// parsing a `Str`.
+macro_rules! deserialize_float {
+    ($method:ident, $visit:ident, $ty:ty) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            match self.value {
+                ConfigValue::Float(f) => visitor.$visit(f.into_inner() as $ty),
+                ConfigValue::Int(i) => visitor.$visit(*i as $ty),
+                ConfigValue::Str(s) => {
+                    let parsed: $ty = s.parse().map_err(|_| {
+                        ConfigError::custom(format!("expected a {}, got: '{}'", stringify!($ty), s))
+                    })?;
+                    visitor.$visit(parsed)
+                }
+                other => Err(ConfigError::custom(format!(
+                    "expected a {}, got: {:?}",
+                    stringify!($ty),
+                    other
+                ))),
+            }
+        }
+    };
+}
+
+impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
+    type Error = ConfigError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            ConfigValue::Table(_) => self.deserialize_map(visitor),
+            ConfigValue::Str(s) => visitor.visit_str(s),
+            ConfigValue::Bool(b) => visitor.visit_bool(*b),
+            ConfigValue::Int(i) => visitor.visit_i64(*i),
+            ConfigValue::Float(f) => visitor.visit_f64(f.into_inner()),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            ConfigValue::Bool(b) => visitor.visit_bool(*b),
+            ConfigValue::Str(s) => {
+                let parsed: bool = s
+                    .parse()
+                    .map_err(|_| ConfigError::custom(format!("expected a bool, got: '{}'", s)))?;
+                visitor.visit_bool(parsed)
+            }
+            other => Err(ConfigError::custom(format!("expected a bool, got: {:?}", other))),
+        }
+    }
+
+    deserialize_int!(deserialize_i8, visit_i8, i8);
+    deserialize_int!(deserialize_i16, visit_i16, i16);
+    deserialize_int!(deserialize_i32, visit_i32, i32);
+    deserialize_int!(deserialize_i64, visit_i64, i64);
+    deserialize_int!(deserialize_u8, visit_u8, u8);
+    deserialize_int!(deserialize_u16, visit_u16, u16);
+    deserialize_int!(deserialize_u32, visit_u32, u32);
+    deserialize_int!(deserialize_u64, visit_u64, u64);
+    deserialize_float!(deserialize_f32, visit_f32, f32);
+    deserialize_float!(deserialize_f64, visit_f64, f64);
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            ConfigValue::Str(s) => visitor.visit_str(s),
+            ConfigValue::Bool(b) => visitor.visit_str(&b.to_string()),
+            ConfigValue::Int(i) => visitor.visit_str(&i.to_string()),
+            ConfigValue::Float(f) => visitor.visit_str(&f.to_string()),
+            ConfigValue::Table(_) => Err(ConfigError::custom(
+                "expected a scalar value, found a nested table",
+            )),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            ConfigValue::Str(s) => visitor.visit_string(s.clone()),
+            ConfigValue::Bool(b) => visitor.visit_string(b.to_string()),
+            ConfigValue::Int(i) => visitor.visit_string(i.to_string()),
+            ConfigValue::Float(f) => visitor.visit_string(f.to_string()),
+            ConfigValue::Table(_) => Err(ConfigError::custom(
+                "expected a scalar value, found a nested table",
+            )),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            ConfigValue::Table(map) => visitor.visit_map(ConfigMapAccess {
+                iter: map.iter(),
+                value: None,
+            }),
+            other => Err(ConfigError::custom(format!("expected a table, got: {:?}", other))),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        char bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::insert_nested_key;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct LogConfig {
+        level: String,
+        max_files: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct AppConfig {
+        is_active: bool,
+        log: LogConfig,
+    }
+
+    #[test]
+    fn test_from_config_coerces_field_types() {
+        let mut root = BTreeMap::new();
+        insert_nested_key(&mut root, "is_active", "true");
+        insert_nested_key(&mut root, "log.level", "info");
+        insert_nested_key(&mut root, "log.max_files", "5");
+
+        let config: AppConfig = from_config(&root).expect("deserialize should succeed");
+
+        assert_eq!(
+            config,
+            AppConfig {
+                is_active: true,
+                log: LogConfig {
+                    level: "info".to_string(),
+                    max_files: 5,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_config_rejects_bad_bool() {
+        let mut root = BTreeMap::new();
+        insert_nested_key(&mut root, "is_active", "not-a-bool");
+        insert_nested_key(&mut root, "log.level", "info");
+        insert_nested_key(&mut root, "log.max_files", "5");
+
+        let result: Result<AppConfig, ConfigError> = from_config(&root);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_from_config_accepts_native_typed_values() {
+        let mut root = BTreeMap::new();
+        root.insert("is_active".to_string(), ConfigValue::Bool(true));
+        let mut log = BTreeMap::new();
+        log.insert("level".to_string(), ConfigValue::Str("info".to_string()));
+        log.insert("max_files".to_string(), ConfigValue::Int(5));
+        root.insert("log".to_string(), ConfigValue::Table(log));
+
+        let config: AppConfig = from_config(&root).expect("deserialize should succeed");
+
+        assert_eq!(
+            config,
+            AppConfig {
+                is_active: true,
+                log: LogConfig {
+                    level: "info".to_string(),
+                    max_files: 5,
+                },
+            }
+        );
+    }
+}
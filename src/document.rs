@@ -0,0 +1,227 @@
+//! A fidelity-preserving document model: unlike `parse_sysctl_conf_to_nested`,
+//! which discards comments and blank lines, `ConfigDocument` keeps them
+//! alongside the settings and the original key ordering, so a single value
+//! can be edited without destroying the rest of the file.
+
+use std::collections::BTreeMap;
+
+use crate::{config_error_from_nom, insert_nested_key, parse_raw_lines, ConfigError, ConfigValue, ParsedLine};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DocItem {
+    Comment(String),
+    Blank,
+    Setting {
+        key: String,
+        value: String,
+        trailing_comment: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConfigDocument {
+    pub items: Vec<DocItem>,
+}
+
+impl ConfigDocument {
+    /// Parse `.conf` text into a document that keeps every comment and blank
+    /// line in its original position. Fails with the line/column of the
+    /// first line that is neither blank, a comment, nor `key = value`.
+    pub fn parse(input: &str) -> Result<Self, ConfigError> {
+        let (_, lines) =
+            parse_raw_lines(input).map_err(|e| config_error_from_nom(input, e))?;
+        let items = lines
+            .into_iter()
+            .map(|line| match line {
+                ParsedLine::Comment(comment) => DocItem::Comment(comment),
+                ParsedLine::Empty => DocItem::Blank,
+                ParsedLine::Setting(key, value) => {
+                    let (value, trailing_comment) = split_trailing_comment(&value.node);
+                    DocItem::Setting {
+                        key: key.node,
+                        value,
+                        trailing_comment,
+                    }
+                }
+            })
+            .collect();
+        Ok(ConfigDocument { items })
+    }
+
+    /// Flatten this document's settings into a nested map shaped like the one
+    /// `parse_sysctl_conf_to_nested` produces, discarding comments and blanks.
+    /// Unlike that parser, a value's inline `# comment` is not part of the
+    /// stored string here, since `parse` already split it off.
+    pub fn to_nested(&self) -> BTreeMap<String, ConfigValue> {
+        let mut root = BTreeMap::new();
+        for item in &self.items {
+            if let DocItem::Setting { key, value, .. } = item {
+                insert_nested_key(&mut root, key, value);
+            }
+        }
+        root
+    }
+
+    /// Render this document back to `.conf` text, keeping comments, blank
+    /// lines, and key ordering as parsed (or as edited via `items`). Spacing
+    /// is normalised (`# comment`, `key = value`, no indentation) and the
+    /// output has no trailing newline.
+    pub fn render(&self) -> String {
+        let lines: Vec<String> = self
+            .items
+            .iter()
+            .map(|item| match item {
+                DocItem::Comment(comment) => format!("# {}", comment),
+                DocItem::Blank => String::new(),
+                DocItem::Setting {
+                    key,
+                    value,
+                    trailing_comment,
+                } => match trailing_comment {
+                    Some(comment) => format!("{} = {} # {}", key, value, comment),
+                    None => format!("{} = {}", key, value),
+                },
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
+
+/// Split a setting's raw value on its first `#`, if any, into the value
+/// itself and an inline trailing comment, e.g. `"info # verbose"` becomes
+/// `("info", Some("verbose"))`. There's no quoting syntax for values, so the
+/// first `#` always starts the comment.
+fn split_trailing_comment(value: &str) -> (String, Option<String>) {
+    match value.find('#') {
+        Some(idx) => {
+            let (value, comment) = value.split_at(idx);
+            (value.trim().to_string(), Some(comment[1..].trim().to_string()))
+        }
+        None => (value.to_string(), None),
+    }
+}
+
+/// Serialize a nested config map back to `.conf` text, one `key.sub.leaf = value`
+/// line per leaf, in key order. This does not restore comments or blank lines
+/// stripped out when the map was built; use `ConfigDocument` to edit a file
+/// in place while keeping those.
+pub fn to_sysctl_string(map: &BTreeMap<String, ConfigValue>) -> String {
+    let mut lines = Vec::new();
+    write_lines(map, "", &mut lines);
+    lines.join("\n")
+}
+
+/// Format a float so it always keeps a fractional part (`f64`'s `Display`
+/// prints a whole number like `1.0` as `"1"`, which `parse_typed` would then
+/// re-infer as an `Int` instead of a `Float`).
+fn format_float(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{:.1}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_lines(map: &BTreeMap<String, ConfigValue>, prefix: &str, out: &mut Vec<String>) {
+    for (key, value) in map {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            ConfigValue::Str(s) => out.push(format!("{} = {}", full_key, s)),
+            ConfigValue::Bool(b) => out.push(format!("{} = {}", full_key, b)),
+            ConfigValue::Int(i) => out.push(format!("{} = {}", full_key, i)),
+            ConfigValue::Float(f) => out.push(format!("{} = {}", full_key, format_float(f.into_inner()))),
+            ConfigValue::Table(sub) => write_lines(sub, &full_key, out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_preserves_comments_and_blanks() {
+        let input = "# top comment\nlog.level = info\n\nlog.file = /var/log/console.log\n";
+        let doc = ConfigDocument::parse(input).unwrap();
+        assert_eq!(
+            doc.items,
+            vec![
+                DocItem::Comment("top comment".to_string()),
+                DocItem::Setting {
+                    key: "log.level".to_string(),
+                    value: "info".to_string(),
+                    trailing_comment: None,
+                },
+                DocItem::Blank,
+                DocItem::Setting {
+                    key: "log.file".to_string(),
+                    value: "/var/log/console.log".to_string(),
+                    trailing_comment: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_round_trips_edited_value() {
+        let input = "# keep me\nlog.level = info\n";
+        let mut doc = ConfigDocument::parse(input).unwrap();
+        if let DocItem::Setting { value, .. } = &mut doc.items[1] {
+            *value = "debug".to_string();
+        }
+        assert_eq!(doc.render(), "# keep me\nlog.level = debug");
+    }
+
+    #[test]
+    fn test_parse_extracts_trailing_comment() {
+        let input = "log.level = info # inline comment\n";
+        let doc = ConfigDocument::parse(input).unwrap();
+        assert_eq!(
+            doc.items,
+            vec![DocItem::Setting {
+                key: "log.level".to_string(),
+                value: "info".to_string(),
+                trailing_comment: Some("inline comment".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_render_round_trips_trailing_comment() {
+        let input = "log.level = info # inline comment\n";
+        let doc = ConfigDocument::parse(input).unwrap();
+        assert_eq!(doc.render(), "log.level = info # inline comment");
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_line() {
+        let input = "log.level = info\nnot a setting line at all\n";
+        let err = ConfigDocument::parse(input).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.column, 1);
+    }
+
+    #[test]
+    fn test_to_sysctl_string_round_trips_whole_number_float() {
+        let typed = crate::parse_typed("ratio = 1.0\n").unwrap();
+        let rendered = to_sysctl_string(&typed);
+        assert_eq!(rendered, "ratio = 1.0");
+
+        let reparsed = crate::parse_typed(&rendered).unwrap();
+        assert_eq!(typed, reparsed);
+    }
+
+    #[test]
+    fn test_to_sysctl_string_flattens_nested_map() {
+        let mut root = BTreeMap::new();
+        insert_nested_key(&mut root, "log.level", "info");
+        insert_nested_key(&mut root, "endpoint", "localhost:3000");
+
+        let rendered = to_sysctl_string(&root);
+        assert_eq!(rendered, "endpoint = localhost:3000\nlog.level = info");
+    }
+}
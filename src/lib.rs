@@ -1,65 +1,166 @@
+pub mod de;
+pub mod document;
+pub mod path;
 pub mod schema;
 
+pub use de::from_config;
+pub use document::{to_sysctl_string, ConfigDocument};
+
 use nom::{
     bytes::complete::take_till, character::complete::line_ending, multi::separated_list0, IResult,
 };
+use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ConfigValue {
+    Bool(bool),
+    Int(i64),
+    Float(OrderedFloat<f64>),
     Str(String),
     Table(BTreeMap<String, ConfigValue>),
 }
 
+/// Wraps a parsed node together with the 1-based line/column it came from in
+/// the source `.conf` text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Positioned<T> {
+    pub node: T,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A structured parse failure, carrying the offending line/column instead of
+/// a `Debug`-formatted nom error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "parse error at line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn config_error_from_nom(input: &str, err: nom::Err<nom::error::Error<&str>>) -> ConfigError {
+    match err {
+        nom::Err::Error(e) | nom::Err::Failure(e) => {
+            let consumed = input.len() - e.input.len();
+            let line = input[..consumed].matches('\n').count() + 1;
+            let last_newline = input[..consumed].rfind('\n').map(|p| p + 1).unwrap_or(0);
+            let column = consumed - last_newline + 1;
+            ConfigError {
+                message: format!("{:?}", e.code),
+                line,
+                column,
+            }
+        }
+        nom::Err::Incomplete(_) => ConfigError {
+            message: "incomplete input".to_string(),
+            line: 0,
+            column: 0,
+        },
+    }
+}
+
 /// A utility function to help insert a dotted key into our nested structure.
 pub fn insert_nested_key(root: &mut BTreeMap<String, ConfigValue>, key: &str, val: &str) {
     let parts: Vec<&str> = key.split('.').collect();
-    insert_recursive(root, &parts, val);
+    insert_recursive(root, &parts, val, &|v| ConfigValue::Str(v.to_string()));
 }
 
-fn insert_recursive(current_map: &mut BTreeMap<String, ConfigValue>, parts: &[&str], val: &str) {
+/// Shared tree-descent logic for `insert_nested_key`/`insert_nested_key_typed`:
+/// walk `parts` into `current_map`, creating intermediate tables as needed,
+/// and build the final leaf with `make_leaf` (a plain `Str` for the former,
+/// `infer_value` for the latter).
+fn insert_recursive(
+    current_map: &mut BTreeMap<String, ConfigValue>,
+    parts: &[&str],
+    val: &str,
+    make_leaf: &impl Fn(&str) -> ConfigValue,
+) {
     if parts.len() == 1 {
-        current_map.insert(parts[0].to_string(), ConfigValue::Str(val.to_string()));
+        current_map.insert(parts[0].to_string(), make_leaf(val));
         return;
     }
 
     let head = parts[0];
     let tail = &parts[1..];
 
-    // Check if this key already exists
-    if let Some(existing_value) = current_map.get_mut(head) {
-        if let ConfigValue::Table(ref mut sub_map) = existing_value {
-            insert_recursive(sub_map, tail, val);
-            return;
-        } else {
-            // If it was previously a string, overwrite it
+    match current_map.get_mut(head) {
+        Some(ConfigValue::Table(sub_map)) => insert_recursive(sub_map, tail, val, make_leaf),
+        Some(existing_value) => {
+            // If it was previously a scalar, overwrite it
             let mut new_map = BTreeMap::new();
-            insert_recursive(&mut new_map, tail, val);
+            insert_recursive(&mut new_map, tail, val, make_leaf);
             *existing_value = ConfigValue::Table(new_map);
-            return;
         }
-    } else {
-        let mut new_map = BTreeMap::new();
-        insert_recursive(&mut new_map, tail, val);
-        current_map.insert(head.to_string(), ConfigValue::Table(new_map));
+        None => {
+            let mut new_map = BTreeMap::new();
+            insert_recursive(&mut new_map, tail, val, make_leaf);
+            current_map.insert(head.to_string(), ConfigValue::Table(new_map));
+        }
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum ParsedLine {
+/// Infer the narrowest `ConfigValue` a raw string represents: `"true"`/`"false"`
+/// become `Bool`, a value that parses as an `i64` becomes `Int`, any other
+/// value that parses as an `f64` (which includes forms like `1e3`, `inf` and
+/// `NaN`) becomes `Float`, and anything else stays a `Str`.
+fn infer_value(val: &str) -> ConfigValue {
+    match val {
+        "true" => return ConfigValue::Bool(true),
+        "false" => return ConfigValue::Bool(false),
+        _ => {}
+    }
+    if let Ok(i) = val.parse::<i64>() {
+        return ConfigValue::Int(i);
+    }
+    if let Ok(f) = val.parse::<f64>() {
+        return ConfigValue::Float(OrderedFloat(f));
+    }
+    ConfigValue::Str(val.to_string())
+}
+
+/// Like `insert_nested_key`, but infers the narrowest `ConfigValue` variant
+/// for the leaf (`Bool`, `Int`, `Float`, falling back to `Str`) instead of
+/// always storing a string.
+pub fn insert_nested_key_typed(root: &mut BTreeMap<String, ConfigValue>, key: &str, val: &str) {
+    let parts: Vec<&str> = key.split('.').collect();
+    insert_recursive(root, &parts, val, &infer_value);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ParsedLine {
     /// Comment line (ignored later)
     Comment(String),
-    /// Setting "key = value"
-    Setting(String, String),
+    /// Setting "key = value", each side carrying its source position
+    Setting(Positioned<String>, Positioned<String>),
     /// Empty (whitespace-only) line
     Empty,
 }
 
-/// Parse a single line
+/// Parse a single line, computing the column of the key and the value
+/// relative to the start of the (untrimmed) line. The line number is filled
+/// in afterwards by `parse_config_lines`, which knows the line's index.
+///
+/// A line that is neither blank, a comment, nor a `key = value` pair is a
+/// real parse failure (not silently dropped), so it surfaces through
+/// `config_error_from_nom` with the line/column it occurred at.
 fn parse_line_content(input: &str) -> IResult<&str, ParsedLine> {
-    let trimmed = input.trim_start();
+    let indent = input.len() - input.trim_start().len();
+    let trimmed = &input[indent..];
     if trimmed.is_empty() {
         return Ok(("", ParsedLine::Empty));
     }
@@ -71,15 +172,36 @@ fn parse_line_content(input: &str) -> IResult<&str, ParsedLine> {
 
     // Try to find '='
     if let Some(eq_pos) = trimmed.find('=') {
-        let key = &trimmed[..eq_pos];
-        let value = &trimmed[eq_pos + 1..];
+        let key_raw = &trimmed[..eq_pos];
+        let value_raw = &trimmed[eq_pos + 1..];
+
+        let key_indent = key_raw.len() - key_raw.trim_start().len();
+        let key_column = indent + key_indent + 1;
+
+        let value_indent = value_raw.len() - value_raw.trim_start().len();
+        let value_column = indent + eq_pos + 1 + value_indent + 1;
+
         return Ok((
             "",
-            ParsedLine::Setting(key.trim().to_string(), value.trim().to_string()),
+            ParsedLine::Setting(
+                Positioned {
+                    node: key_raw.trim().to_string(),
+                    line: 0,
+                    column: key_column,
+                },
+                Positioned {
+                    node: value_raw.trim().to_string(),
+                    line: 0,
+                    column: value_column,
+                },
+            ),
         ));
     }
 
-    Ok(("", ParsedLine::Empty))
+    Err(nom::Err::Error(nom::error::Error {
+        input: trimmed,
+        code: nom::error::ErrorKind::Tag,
+    }))
 }
 
 /// Parse exactly one line up to newline.
@@ -91,37 +213,113 @@ fn parse_line(input: &str) -> IResult<&str, ParsedLine> {
     Ok((remaining, parsed))
 }
 
-fn parse_config_lines(input: &str) -> IResult<&str, Vec<(String, String)>> {
-    let (remaining, lines) = separated_list0(
+/// Parse every line of `input`, numbering each `ParsedLine::Setting`'s key and
+/// value 1-based by its position in the file. Unlike `parse_config_lines`,
+/// comments and blank lines are kept, so callers that need full-fidelity
+/// round-tripping (see the `document` module) can use this directly.
+pub(crate) fn parse_raw_lines(input: &str) -> IResult<&str, Vec<ParsedLine>> {
+    let (remaining, mut lines) = separated_list0(
         // we separate by line_ending
         line_ending,
         parse_line,
     )(input)?;
 
-    // Filter to keep only Settings
-    let mut settings = Vec::new();
-    for line in lines {
-        if let ParsedLine::Setting(k, v) = line {
-            settings.push((k, v));
-        }
+    // `separated_list0` always tries the line parser once more on whatever is
+    // left after the final separator. When `input` ends in a line terminator
+    // that remainder is "", which parses as a trivial `Empty` that doesn't
+    // correspond to a real line in the file - drop it.
+    if (input.ends_with('\n') || input.ends_with('\r')) && matches!(lines.last(), Some(ParsedLine::Empty)) {
+        lines.pop();
     }
 
+    let lines = lines
+        .into_iter()
+        .enumerate()
+        .map(|(idx, line)| match line {
+            ParsedLine::Setting(mut key, mut value) => {
+                key.line = idx + 1;
+                value.line = idx + 1;
+                ParsedLine::Setting(key, value)
+            }
+            other => other,
+        })
+        .collect();
+
     // Optionally consume any trailing newline or whitespace
     let (remaining, _) = nom::combinator::opt(line_ending)(remaining)?;
 
+    // `separated_list0` stops (without propagating an error) as soon as
+    // `parse_line` fails, leaving the offending line and everything after it
+    // in `remaining`. Surface that as a real parse failure instead of
+    // silently truncating the document.
+    if !remaining.is_empty() {
+        return Err(nom::Err::Error(nom::error::Error {
+            input: remaining,
+            code: nom::error::ErrorKind::Tag,
+        }));
+    }
+
+    Ok((remaining, lines))
+}
+
+fn parse_config_lines(
+    input: &str,
+) -> IResult<&str, Vec<(Positioned<String>, Positioned<String>)>> {
+    let (remaining, lines) = parse_raw_lines(input)?;
+
+    // Filter to keep only Settings.
+    let mut settings = Vec::new();
+    for line in lines {
+        if let ParsedLine::Setting(key, value) = line {
+            settings.push((key, value));
+        }
+    }
+
     Ok((remaining, settings))
 }
 
-pub fn parse_sysctl_conf_to_nested(input: &str) -> Result<BTreeMap<String, ConfigValue>, String> {
+/// Parse `.conf` text into a nested map, also returning the source position
+/// of every setting's value keyed by its full dotted path (e.g. `"log.level"`).
+pub fn parse_sysctl_conf_with_positions(
+    input: &str,
+) -> Result<
+    (
+        BTreeMap<String, ConfigValue>,
+        BTreeMap<String, Positioned<String>>,
+    ),
+    ConfigError,
+> {
+    match parse_config_lines(input) {
+        Ok((_, kvs)) => {
+            let mut root = BTreeMap::new();
+            let mut field_positions = BTreeMap::new();
+            for (key, value) in kvs {
+                insert_nested_key(&mut root, &key.node, &value.node);
+                field_positions.insert(key.node, value);
+            }
+            Ok((root, field_positions))
+        }
+        Err(e) => Err(config_error_from_nom(input, e)),
+    }
+}
+
+pub fn parse_sysctl_conf_to_nested(input: &str) -> Result<BTreeMap<String, ConfigValue>, ConfigError> {
+    parse_sysctl_conf_with_positions(input).map(|(root, _)| root)
+}
+
+/// Parse `.conf` text the same way as `parse_sysctl_conf_to_nested`, but infer
+/// `Bool`/`Int`/`Float` leaves via `insert_nested_key_typed` instead of
+/// leaving every value as a `Str`.
+pub fn parse_typed(input: &str) -> Result<BTreeMap<String, ConfigValue>, ConfigError> {
     match parse_config_lines(input) {
         Ok((_, kvs)) => {
             let mut root = BTreeMap::new();
-            for (k, v) in kvs {
-                insert_nested_key(&mut root, &k, &v);
+            for (key, value) in kvs {
+                insert_nested_key_typed(&mut root, &key.node, &value.node);
             }
             Ok(root)
         }
-        Err(e) => Err(format!("Parse error: {:?}", e)),
+        Err(e) => Err(config_error_from_nom(input, e)),
     }
 }
 
@@ -129,6 +327,8 @@ pub fn to_json_string(map: &BTreeMap<String, ConfigValue>) -> String {
     serde_json::to_string_pretty(map).unwrap()
 }
 
+/// Parse `.conf` text into a nested map, keeping every leaf a `Str` (the
+/// original behavior, kept for callers that don't want type inference).
 pub fn parse_to_map(input: &str) -> BTreeMap<String, ConfigValue> {
     parse_sysctl_conf_to_nested(input).unwrap()
 }
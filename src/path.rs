@@ -0,0 +1,239 @@
+//! A small path-query language for pulling values out of the nested config
+//! map without manually matching `Table`s level by level.
+
+use std::collections::BTreeMap;
+
+use crate::ConfigValue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    /// A literal key, e.g. the `log` in `log.level`.
+    Key(String),
+    /// `*`: matches every key at this level.
+    Wildcard,
+    /// `**`: matches zero or more levels, recursing into every descendant table.
+    RecursiveWildcard,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    pub segments: Vec<PathSegment>,
+    /// An optional `[= value]` suffix restricting matches to leaves equal to `value`.
+    pub predicate: Option<String>,
+}
+
+/// Parse a path string like `"log.level"`, `"net.*.forward"`, or
+/// `"**.enabled"`, optionally followed by a `[= value]` predicate.
+pub fn parse_path(input: &str) -> Path {
+    let (path_part, predicate) = match input.find('[') {
+        Some(idx) => {
+            let predicate_body = input[idx + 1..].trim_end_matches(']').trim();
+            let value = predicate_body.strip_prefix('=').unwrap_or(predicate_body).trim();
+            (&input[..idx], Some(value.to_string()))
+        }
+        None => (input, None),
+    };
+
+    let segments = path_part
+        .split('.')
+        .map(|segment| match segment {
+            "*" => PathSegment::Wildcard,
+            "**" => PathSegment::RecursiveWildcard,
+            key => PathSegment::Key(key.to_string()),
+        })
+        .collect();
+
+    Path { segments, predicate }
+}
+
+fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// Walk `map` matching `segments` against it, appending every match (leaf or
+/// subtree) together with its full dotted path to `out`.
+fn select_rec<'a>(
+    map: &'a BTreeMap<String, ConfigValue>,
+    path_so_far: &str,
+    segments: &[PathSegment],
+    out: &mut Vec<(String, &'a ConfigValue)>,
+) {
+    match segments.split_first() {
+        None => {}
+        Some((PathSegment::Key(key), rest)) => {
+            if let Some(value) = map.get(key) {
+                emit_or_descend(value, &join_path(path_so_far, key), rest, out);
+            }
+        }
+        Some((PathSegment::Wildcard, rest)) => {
+            for (key, value) in map {
+                emit_or_descend(value, &join_path(path_so_far, key), rest, out);
+            }
+        }
+        Some((PathSegment::RecursiveWildcard, rest)) => {
+            // `**` may consume zero levels: try the remainder directly against this map.
+            select_rec(map, path_so_far, rest, out);
+            // `**` may consume one more level: recurse into every nested table, keeping `**` active.
+            for (key, value) in map {
+                if let ConfigValue::Table(sub) = value {
+                    select_rec(sub, &join_path(path_so_far, key), segments, out);
+                }
+            }
+        }
+    }
+}
+
+fn emit_or_descend<'a>(
+    value: &'a ConfigValue,
+    path_so_far: &str,
+    rest: &[PathSegment],
+    out: &mut Vec<(String, &'a ConfigValue)>,
+) {
+    if rest.is_empty() {
+        out.push((path_so_far.to_string(), value));
+        return;
+    }
+    if let ConfigValue::Table(sub) = value {
+        select_rec(sub, path_so_far, rest, out);
+    }
+}
+
+/// Select every value in `root` matching `path`, paired with its full dotted
+/// path. Replaces the nested `if let ConfigValue::Table(...)` chains callers
+/// would otherwise need to write by hand.
+pub fn select<'a>(
+    root: &'a BTreeMap<String, ConfigValue>,
+    path: &Path,
+) -> Vec<(String, &'a ConfigValue)> {
+    let mut out = Vec::new();
+    select_rec(root, "", &path.segments, &mut out);
+    if let Some(expected) = &path.predicate {
+        out.retain(|(_, value)| value_matches(value, expected));
+    }
+    out
+}
+
+/// Compare a leaf against a predicate's expected string, stringifying the
+/// leaf so `net.*.forward[= true]` matches a native `ConfigValue::Bool(true)`
+/// the same way it matches the string `"true"`.
+fn value_matches(value: &ConfigValue, expected: &str) -> bool {
+    match value {
+        ConfigValue::Str(s) => s == expected,
+        ConfigValue::Bool(b) => b.to_string() == expected,
+        ConfigValue::Int(i) => i.to_string() == expected,
+        ConfigValue::Float(f) => f.to_string() == expected,
+        ConfigValue::Table(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::insert_nested_key;
+
+    fn sample_map() -> BTreeMap<String, ConfigValue> {
+        let mut root = BTreeMap::new();
+        insert_nested_key(&mut root, "log.level", "info");
+        insert_nested_key(&mut root, "net.eth0.forward", "true");
+        insert_nested_key(&mut root, "net.eth1.forward", "false");
+        insert_nested_key(&mut root, "net.eth1.enabled", "true");
+        insert_nested_key(&mut root, "deep.a.b.enabled", "true");
+        root
+    }
+
+    #[test]
+    fn test_parse_literal_path() {
+        let path = parse_path("log.level");
+        assert_eq!(
+            path,
+            Path {
+                segments: vec![
+                    PathSegment::Key("log".into()),
+                    PathSegment::Key("level".into())
+                ],
+                predicate: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_wildcard_and_predicate() {
+        let path = parse_path("net.*.forward[= true]");
+        assert_eq!(
+            path,
+            Path {
+                segments: vec![
+                    PathSegment::Key("net".into()),
+                    PathSegment::Wildcard,
+                    PathSegment::Key("forward".into()),
+                ],
+                predicate: Some("true".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_select_literal_path() {
+        let root = sample_map();
+        let results = select(&root, &parse_path("log.level"));
+        assert_eq!(
+            results,
+            vec![("log.level".to_string(), &ConfigValue::Str("info".to_string()))]
+        );
+    }
+
+    #[test]
+    fn test_select_wildcard() {
+        let root = sample_map();
+        let mut results = select(&root, &parse_path("net.*.forward"));
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            results,
+            vec![
+                ("net.eth0.forward".to_string(), &ConfigValue::Str("true".to_string())),
+                ("net.eth1.forward".to_string(), &ConfigValue::Str("false".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_recursive_wildcard() {
+        let root = sample_map();
+        let mut results = select(&root, &parse_path("**.enabled"));
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            results,
+            vec![
+                ("deep.a.b.enabled".to_string(), &ConfigValue::Str("true".to_string())),
+                ("net.eth1.enabled".to_string(), &ConfigValue::Str("true".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_with_predicate() {
+        let root = sample_map();
+        let results = select(&root, &parse_path("net.*.forward[= true]"));
+        assert_eq!(
+            results,
+            vec![("net.eth0.forward".to_string(), &ConfigValue::Str("true".to_string()))]
+        );
+    }
+
+    #[test]
+    fn test_select_with_predicate_matches_typed_leaves() {
+        let mut root = BTreeMap::new();
+        crate::insert_nested_key_typed(&mut root, "net.eth0.forward", "true");
+        crate::insert_nested_key_typed(&mut root, "net.eth1.forward", "false");
+
+        let results = select(&root, &parse_path("net.*.forward[= true]"));
+        assert_eq!(
+            results,
+            vec![("net.eth0.forward".to_string(), &ConfigValue::Bool(true))]
+        );
+    }
+}
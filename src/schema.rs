@@ -4,17 +4,97 @@ use nom::{
     IResult,
     branch::alt,
     bytes::complete::{tag, take_while1},
-    character::complete::{line_ending, space0},
-    combinator::map,
+    character::complete::{char, digit1, line_ending, space0},
+    combinator::{map, opt},
     multi::separated_list0,
+    sequence::delimited,
 };
 
-use super::ConfigValue;
+use super::{ConfigValue, Positioned};
+
+/// An inclusive lower/upper bound pair for a numeric scheme type. Either side
+/// may be absent to mean "unbounded", e.g. `1..` or `..=100`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntRange {
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatRange {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+fn format_int_range(range: &IntRange) -> String {
+    match (range.min, range.max) {
+        (Some(min), Some(max)) => format!("{}..={}", min, max),
+        (Some(min), None) => format!("{}..", min),
+        (None, Some(max)) => format!("..={}", max),
+        (None, None) => "..".to_string(),
+    }
+}
+
+fn format_float_range(range: &FloatRange) -> String {
+    match (range.min, range.max) {
+        (Some(min), Some(max)) => format!("{}..={}", min, max),
+        (Some(min), None) => format!("{}..", min),
+        (None, Some(max)) => format!("..={}", max),
+        (None, None) => "..".to_string(),
+    }
+}
+
+fn check_int_range(field_name: &str, location: &str, range: &IntRange, value: i64) -> Result<(), String> {
+    if range.min.is_some_and(|min| value < min) || range.max.is_some_and(|max| value > max) {
+        return Err(format!(
+            "Field '{}'{} = {} is outside range {}",
+            field_name,
+            location,
+            value,
+            format_int_range(range)
+        ));
+    }
+    Ok(())
+}
+
+fn check_float_range(
+    field_name: &str,
+    location: &str,
+    range: &FloatRange,
+    value: f64,
+) -> Result<(), String> {
+    if range.min.is_some_and(|min| value < min) || range.max.is_some_and(|max| value > max) {
+        return Err(format!(
+            "Field '{}'{} = {} is outside range {}",
+            field_name,
+            location,
+            value,
+            format_float_range(range)
+        ));
+    }
+    Ok(())
+}
+
+/// Stringify a scalar `ConfigValue` for comparison against an `Enum`'s
+/// members, so a schema's string members match a `parse_typed` leaf the same
+/// way they'd match the equivalent `Str`.
+fn scalar_to_string(value: &ConfigValue) -> Option<String> {
+    match value {
+        ConfigValue::Str(s) => Some(s.clone()),
+        ConfigValue::Bool(b) => Some(b.to_string()),
+        ConfigValue::Int(i) => Some(i.to_string()),
+        ConfigValue::Float(f) => Some(f.to_string()),
+        ConfigValue::Table(_) => None,
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum SchemeType {
     Bool,
     String,
+    Int(IntRange),
+    Float(FloatRange),
+    Enum(Vec<String>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -23,8 +103,83 @@ pub struct SchemeField {
     pub field_type: SchemeType,
 }
 
+fn parse_i64(input: &str) -> IResult<&str, i64> {
+    let (input, sign) = opt(char('-'))(input)?;
+    let (input, digits) = digit1(input)?;
+    let value: i64 = digits.parse().expect("digit1 only matches digits");
+    Ok((input, if sign.is_some() { -value } else { value }))
+}
+
+fn parse_f64(input: &str) -> IResult<&str, f64> {
+    let (input, sign) = opt(char('-'))(input)?;
+    let (input, int_part) = digit1(input)?;
+    let (input, frac_part) = opt(nom::sequence::preceded(char('.'), digit1))(input)?;
+    let value: f64 = match frac_part {
+        Some(frac) => format!("{}.{}", int_part, frac).parse().expect("digit1 only matches digits"),
+        None => int_part.parse().expect("digit1 only matches digits"),
+    };
+    Ok((input, if sign.is_some() { -value } else { value }))
+}
+
+/// Parse a range like `0..=65535`, `1..` or `..=100`. Whether `=` is present
+/// or not, the upper bound (when given) is treated as inclusive.
+fn parse_int_range(input: &str) -> IResult<&str, IntRange> {
+    let (input, min) = opt(parse_i64)(input)?;
+    let (input, _) = tag("..")(input)?;
+    let (input, _) = opt(char('='))(input)?;
+    let (input, max) = opt(parse_i64)(input)?;
+    Ok((input, IntRange { min, max }))
+}
+
+fn parse_float_range(input: &str) -> IResult<&str, FloatRange> {
+    let (input, min) = opt(parse_f64)(input)?;
+    let (input, _) = tag("..")(input)?;
+    let (input, _) = opt(char('='))(input)?;
+    let (input, max) = opt(parse_f64)(input)?;
+    Ok((input, FloatRange { min, max }))
+}
+
+fn parse_int_type(input: &str) -> IResult<&str, SchemeType> {
+    let (input, _) = tag("int")(input)?;
+    let (input, range) = opt(delimited(char('('), parse_int_range, char(')')))(input)?;
+    Ok((
+        input,
+        SchemeType::Int(range.unwrap_or(IntRange { min: None, max: None })),
+    ))
+}
+
+fn parse_float_type(input: &str) -> IResult<&str, SchemeType> {
+    let (input, _) = tag("float")(input)?;
+    let (input, range) = opt(delimited(char('('), parse_float_range, char(')')))(input)?;
+    Ok((
+        input,
+        SchemeType::Float(range.unwrap_or(FloatRange { min: None, max: None })),
+    ))
+}
+
+fn parse_enum_type(input: &str) -> IResult<&str, SchemeType> {
+    let (input, _) = tag("enum")(input)?;
+    let (input, _) = char('(')(input)?;
+    let (input, members) = separated_list0(
+        alt((tag(","), tag("|"))),
+        map(
+            delimited(
+                space0,
+                take_while1(|c: char| c.is_alphanumeric() || c == '_' || c == '-'),
+                space0,
+            ),
+            |s: &str| s.to_string(),
+        ),
+    )(input)?;
+    let (input, _) = char(')')(input)?;
+    Ok((input, SchemeType::Enum(members)))
+}
+
 fn parse_field_type(input: &str) -> IResult<&str, SchemeType> {
     alt((
+        parse_enum_type,
+        parse_int_type,
+        parse_float_type,
         map(tag("bool"), |_| SchemeType::Bool),
         map(tag("string"), |_| SchemeType::String),
     ))(input)
@@ -57,14 +212,22 @@ pub fn parse(input: &str) -> Vec<SchemeField> {
 }
 
 
-/// Validate a config against a schema
+/// Validate a config against a schema. `field_positions` maps a field's full
+/// dotted path to where its value came from in the source text, so errors can
+/// point at a line instead of just a field name; pass an empty map if that
+/// information isn't available.
 pub fn validate_config(
     schema: &[SchemeField],
-    config: &BTreeMap<String, ConfigValue>
+    config: &BTreeMap<String, ConfigValue>,
+    field_positions: &BTreeMap<String, Positioned<String>>,
 ) -> Result<(), String> {
     for field in schema {
         let field_name = &field.name;
         let field_type = &field.field_type;
+        let location = field_positions
+            .get(field_name)
+            .map(|p| format!(" (line {})", p.line))
+            .unwrap_or_default();
 
         // Check presence
         let value = match config.get(field_name) {
@@ -78,36 +241,118 @@ pub fn validate_config(
         match field_type {
             SchemeType::Bool => {
                 match value {
+                    ConfigValue::Bool(_) => {}
                     ConfigValue::Str(s) => {
                         // Must be strictly "true" or "false"
                         if s != "true" && s != "false" {
                             return Err(format!(
-                                "Field '{}' must be a bool ('true'/'false'), got: '{}'",
-                                field_name, s
+                                "{}{}: must be a bool ('true'/'false'), got: '{}'",
+                                field_name, location, s
                             ));
                         }
                     }
+                    ConfigValue::Int(_) | ConfigValue::Float(_) => {
+                        return Err(format!(
+                            "{}{}: must be a bool, but found a number.",
+                            field_name, location
+                        ));
+                    }
                     ConfigValue::Table(_) => {
                         return Err(format!(
-                            "Field '{}' must be a bool, but found a nested table.",
-                            field_name
+                            "{}{}: must be a bool, but found a nested table.",
+                            field_name, location
                         ));
                     }
                 }
             }
             SchemeType::String => {
                 match value {
-                    ConfigValue::Str(_) => {
-                        // any string is OK
+                    ConfigValue::Str(_)
+                    | ConfigValue::Bool(_)
+                    | ConfigValue::Int(_)
+                    | ConfigValue::Float(_) => {
+                        // any scalar is OK
+                    }
+                    ConfigValue::Table(_) => {
+                        return Err(format!(
+                            "{}{}: must be a string, but found a nested table.",
+                            field_name, location
+                        ));
+                    }
+                }
+            }
+            SchemeType::Int(range) => {
+                match value {
+                    ConfigValue::Int(i) => check_int_range(field_name, &location, range, *i)?,
+                    ConfigValue::Str(s) => {
+                        let parsed: i64 = s.parse().map_err(|_| {
+                            format!("{}{}: must be an int, got: '{}'", field_name, location, s)
+                        })?;
+                        check_int_range(field_name, &location, range, parsed)?;
+                    }
+                    ConfigValue::Bool(_) | ConfigValue::Float(_) => {
+                        return Err(format!(
+                            "{}{}: must be an int, but found a non-int value.",
+                            field_name, location
+                        ));
+                    }
+                    ConfigValue::Table(_) => {
+                        return Err(format!(
+                            "{}{}: must be an int, but found a nested table.",
+                            field_name, location
+                        ));
+                    }
+                }
+            }
+            SchemeType::Float(range) => {
+                match value {
+                    ConfigValue::Float(f) => {
+                        check_float_range(field_name, &location, range, f.into_inner())?
+                    }
+                    ConfigValue::Int(i) => {
+                        check_float_range(field_name, &location, range, *i as f64)?
+                    }
+                    ConfigValue::Str(s) => {
+                        let parsed: f64 = s.parse().map_err(|_| {
+                            format!("{}{}: must be a float, got: '{}'", field_name, location, s)
+                        })?;
+                        check_float_range(field_name, &location, range, parsed)?;
+                    }
+                    ConfigValue::Bool(_) => {
+                        return Err(format!(
+                            "{}{}: must be a float, but found a bool.",
+                            field_name, location
+                        ));
                     }
                     ConfigValue::Table(_) => {
                         return Err(format!(
-                            "Field '{}' must be a string, but found a nested table.",
-                            field_name
+                            "{}{}: must be a float, but found a nested table.",
+                            field_name, location
                         ));
                     }
                 }
             }
+            SchemeType::Enum(members) => match scalar_to_string(value) {
+                Some(s) => {
+                    if !members.iter().any(|m| m == &s) {
+                        return Err(format!(
+                            "Field '{}'{} = '{}' is not one of [{}]",
+                            field_name,
+                            location,
+                            s,
+                            members.join(", ")
+                        ));
+                    }
+                }
+                None => {
+                    return Err(format!(
+                        "{}{}: must be one of [{}], but found a nested table.",
+                        field_name,
+                        location,
+                        members.join(", ")
+                    ));
+                }
+            },
         }
     }
 
@@ -188,7 +433,7 @@ another_flag->bool
         config.insert("username".to_string(), ConfigValue::Str("Alice".to_string()));
 
         // should pass
-        let result = validate_config(&schema, &config);
+        let result = validate_config(&schema, &config, &BTreeMap::new());
         assert!(result.is_ok(), "Expected validation to succeed, got {:?}", result);
     }
 
@@ -205,9 +450,163 @@ another_flag->bool
         config.insert("is_active".to_string(), ConfigValue::Str("false".to_string()));
 
         // should fail because "username" is missing
-        let result = validate_config(&schema, &config);
+        let result = validate_config(&schema, &config, &BTreeMap::new());
         assert!(result.is_err(), "Expected validation to fail, got {:?}", result);
         let err_msg = result.unwrap_err();
         assert!(err_msg.contains("Missing required field"), "Error message mismatch: {}", err_msg);
     }
+
+    #[test]
+    fn test_parse_int_line_with_range() {
+        let input = "port -> int(0..=65535)";
+        let (remain, parsed) = parse_line(input).unwrap();
+        assert!(remain.is_empty());
+        assert_eq!(
+            parsed,
+            SchemeField {
+                name: "port".into(),
+                field_type: SchemeType::Int(IntRange {
+                    min: Some(0),
+                    max: Some(65535)
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_int_line_open_ended_ranges() {
+        let (_, lower_open) = parse_line("retries -> int(..=100)").unwrap();
+        assert_eq!(
+            lower_open.field_type,
+            SchemeType::Int(IntRange { min: None, max: Some(100) })
+        );
+
+        let (_, upper_open) = parse_line("threads -> int(1..)").unwrap();
+        assert_eq!(
+            upper_open.field_type,
+            SchemeType::Int(IntRange { min: Some(1), max: None })
+        );
+    }
+
+    #[test]
+    fn test_parse_float_line_with_range() {
+        let input = "ratio -> float(0..=1)";
+        let (_, parsed) = parse_line(input).unwrap();
+        assert_eq!(
+            parsed.field_type,
+            SchemeType::Float(FloatRange { min: Some(0.0), max: Some(1.0) })
+        );
+    }
+
+    #[test]
+    fn test_parse_enum_line() {
+        let input = "log_level -> enum(info, warn, error)";
+        let (_, parsed) = parse_line(input).unwrap();
+        assert_eq!(
+            parsed.field_type,
+            SchemeType::Enum(vec!["info".into(), "warn".into(), "error".into()])
+        );
+    }
+
+    #[test]
+    fn test_validation_int_out_of_range() {
+        let schema = vec![SchemeField {
+            name: "net.port".into(),
+            field_type: SchemeType::Int(IntRange { min: Some(0), max: Some(65535) }),
+        }];
+
+        let mut config = BTreeMap::new();
+        config.insert("net.port".to_string(), ConfigValue::Str("99999".to_string()));
+
+        let result = validate_config(&schema, &config, &BTreeMap::new());
+        let err_msg = result.unwrap_err();
+        assert!(
+            err_msg.contains("is outside range 0..=65535"),
+            "Error message mismatch: {}",
+            err_msg
+        );
+    }
+
+    #[test]
+    fn test_validation_enum_rejects_unknown_member() {
+        let schema = vec![SchemeField {
+            name: "log_level".into(),
+            field_type: SchemeType::Enum(vec!["info".into(), "warn".into(), "error".into()]),
+        }];
+
+        let mut config = BTreeMap::new();
+        config.insert("log_level".to_string(), ConfigValue::Str("debug".to_string()));
+
+        let result = validate_config(&schema, &config, &BTreeMap::new());
+        let err_msg = result.unwrap_err();
+        assert!(
+            err_msg.contains("is not one of [info, warn, error]"),
+            "Error message mismatch: {}",
+            err_msg
+        );
+    }
+
+    #[test]
+    fn test_validation_enum_accepts_native_typed_value() {
+        let schema = vec![SchemeField {
+            name: "level".into(),
+            field_type: SchemeType::Enum(vec!["1".into(), "2".into(), "3".into()]),
+        }];
+
+        let mut config = BTreeMap::new();
+        config.insert("level".to_string(), ConfigValue::Int(2));
+
+        let result = validate_config(&schema, &config, &BTreeMap::new());
+        assert!(result.is_ok(), "Expected validation to succeed, got {:?}", result);
+    }
+
+    #[test]
+    fn test_validation_accepts_native_typed_values() {
+        let schema = vec![
+            SchemeField { name: "is_active".into(), field_type: SchemeType::Bool },
+            SchemeField {
+                name: "net.port".into(),
+                field_type: SchemeType::Int(IntRange { min: Some(0), max: Some(65535) }),
+            },
+        ];
+
+        let mut config = BTreeMap::new();
+        config.insert("is_active".to_string(), ConfigValue::Bool(true));
+        config.insert("net.port".to_string(), ConfigValue::Int(3000));
+
+        let result = validate_config(&schema, &config, &BTreeMap::new());
+        assert!(result.is_ok(), "Expected validation to succeed, got {:?}", result);
+    }
+
+    #[test]
+    fn test_validation_reports_line_number() {
+        let schema = vec![SchemeField {
+            name: "is_active".into(),
+            field_type: SchemeType::Bool,
+        }];
+
+        let mut config = BTreeMap::new();
+        config.insert(
+            "is_active".to_string(),
+            ConfigValue::Str("nope".to_string()),
+        );
+
+        let mut field_positions = BTreeMap::new();
+        field_positions.insert(
+            "is_active".to_string(),
+            Positioned {
+                node: "nope".to_string(),
+                line: 12,
+                column: 13,
+            },
+        );
+
+        let result = validate_config(&schema, &config, &field_positions);
+        let err_msg = result.unwrap_err();
+        assert!(
+            err_msg.contains("is_active (line 12): must be a bool"),
+            "Error message mismatch: {}",
+            err_msg
+        );
+    }
 }